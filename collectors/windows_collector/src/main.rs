@@ -23,6 +23,12 @@ struct Args {
     #[arg(long, default_value_t = 60)]
     heartbeat_seconds: u64,
 
+    /// With `--send-title`: hold back a resend caused only by a title change until this many
+    /// milliseconds have passed since the last send (the latest title goes out then). App/pid
+    /// changes and heartbeats are never delayed. `0` sends every title change immediately.
+    #[arg(long, default_value_t = 0)]
+    title_debounce_ms: u64,
+
     /// Track background app audio (e.g. QQ Music) via Windows CoreAudio sessions.
     ///
     /// This emits `app_audio` while some non-browser app is producing sound, and `app_audio_stop`
@@ -61,6 +67,34 @@ struct Args {
     /// This prevents attributing long idle time to the last foreground app.
     #[arg(long, default_value_t = 5 * 60)]
     idle_cutoff_seconds: u64,
+
+    /// Never report these executables (basename, case-insensitive), e.g. `--ignore-exe
+    /// KeePassXC.exe,1Password.exe`. Repeatable. Applies to foreground apps and audio apps, so the
+    /// names never leave the machine (stronger than Core-side privacy rules).
+    #[arg(long = "ignore-exe", value_delimiter = ',')]
+    ignore_exe: Vec<String>,
+
+    /// Extra browser executables besides the built-in ones, e.g. `--browser-exe Arc.exe`.
+    /// Repeatable. Their audio is left to the browser extension (tab audio) instead of being
+    /// reported as `app_audio`. Add the same names to Core's `browser_exes` setting.
+    #[arg(long = "browser-exe", value_delimiter = ',')]
+    browser_exe: Vec<String>,
+}
+
+/// How often to re-send `/collector/hello` (config diagnostics on the Core side).
+#[cfg(windows)]
+const HELLO_EVERY_SECONDS: u64 = 10 * 60;
+
+/// How far one poll's sleep may overrun `--poll-ms` on the wall clock before it counts as a clock
+/// jump (machine sleep/resume, or the clock being set) and a `gap` event is sent. Applies both
+/// ways: a clock set back by less than this is treated as ordinary drift.
+const CLOCK_JUMP_TOLERANCE_SECONDS: i64 = 30;
+
+/// Whether a poll whose sleep overran `--poll-ms` by `overrun_ms` on the wall clock (negative if
+/// the clock went back) should be reported as a clock jump.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn is_clock_jump(overrun_ms: i64) -> bool {
+    overrun_ms.abs() > CLOCK_JUMP_TOLERANCE_SECONDS * 1000
 }
 
 #[tokio::main]
@@ -100,6 +134,7 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
     use chrono::{SecondsFormat, Utc};
     use reqwest::Client;
     use serde::Serialize;
+    use std::collections::HashSet;
     use std::path::Path;
     use std::time::Instant;
     use tokio::time::{sleep, Duration};
@@ -128,6 +163,26 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
         pid: u32,
     }
 
+    #[derive(Serialize)]
+    struct ResumeEvent<'a> {
+        v: i32,
+        ts: &'a str,
+        source: &'static str,
+        event: &'static str,
+        app: &'a str,
+        idle_seconds: u64,
+    }
+
+    #[derive(Serialize)]
+    struct GapEvent<'a> {
+        v: i32,
+        ts: &'a str,
+        source: &'static str,
+        event: &'static str,
+        /// Wall-clock time the poll sleep took beyond `--poll-ms` (negative: clock set back).
+        gap_seconds: i64,
+    }
+
     #[derive(Serialize)]
     struct AppAudioEvent<'a> {
         v: i32,
@@ -143,13 +198,45 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
         reason: Option<&'a str>,
     }
 
+    #[derive(Serialize)]
+    struct CollectorHello {
+        source: &'static str,
+        version: &'static str,
+        idle_cutoff_seconds: u64,
+        heartbeat_seconds: u64,
+    }
+
     let client = Client::new();
     let endpoint = format!("{}/event", args.core_url.trim_end_matches('/'));
+    let hello_endpoint = format!("{}/collector/hello", args.core_url.trim_end_matches('/'));
+    let hello = CollectorHello {
+        source: "windows_collector",
+        version: env!("CARGO_PKG_VERSION"),
+        idle_cutoff_seconds: args.idle_cutoff_seconds,
+        heartbeat_seconds: args.heartbeat_seconds,
+    };
+
+    let ignored_exes: HashSet<String> = args
+        .ignore_exe
+        .iter()
+        .map(|s| exe_basename_lower(s.trim()))
+        .filter(|s| !s.is_empty())
+        .collect();
+    if !ignored_exes.is_empty() {
+        info!("ignoring executables: {ignored_exes:?}");
+    }
+    let browser_exes: HashSet<String> = args
+        .browser_exe
+        .iter()
+        .map(|s| exe_basename_lower(s.trim()))
+        .filter(|s| !s.is_empty())
+        .collect();
 
     info!("Windows collector started. Posting to {endpoint}");
 
     let mut last_key: Option<(String, u32, String)> = None; // (app, pid, title)
     let mut last_sent_at = Instant::now();
+    let mut was_idle = false;
 
     let mut last_audio: Option<AudioAppInfo> = None;
     let mut last_audio_sent_at = Instant::now();
@@ -159,12 +246,31 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
         .unwrap_or_else(Instant::now);
     let mut review_snooze_until: Option<Instant> = None;
     let mut last_review_notified_block_id: Option<String> = None;
+    // Re-announce periodically: Core keeps the registry in memory only.
+    let mut last_hello_at: Option<Instant> = None;
 
     loop {
+        let hello_due = match last_hello_at {
+            Some(t) => t.elapsed() >= Duration::from_secs(HELLO_EVERY_SECONDS),
+            None => true,
+        };
+        if hello_due {
+            match client.post(&hello_endpoint).json(&hello).send().await {
+                Ok(res) if res.status().is_success() => last_hello_at = Some(Instant::now()),
+                Ok(res) => {
+                    // Older Core without /collector/hello: don't retry every poll.
+                    last_hello_at = Some(Instant::now());
+                    info!("collector hello rejected: http_{}", res.status().as_u16());
+                }
+                Err(e) => error!("collector hello failed: {e}"),
+            }
+        }
+
         let idle_s = system_idle_seconds();
         if idle_s >= args.idle_cutoff_seconds {
             // Reset key so we emit immediately on resume even if heartbeat isn't due.
             last_key = None;
+            was_idle = true;
         } else {
             let fg = foreground_app();
             let pid = fg.pid;
@@ -177,7 +283,33 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
                 .and_then(|s| s.to_str())
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| format!("pid:{pid}"));
-            if pid != 0 {
+            let ignored = ignored_exes.contains(&app.to_lowercase());
+            if ignored {
+                // Send nothing about this app; re-emit immediately once focus moves on.
+                last_key = None;
+            }
+
+            if was_idle && pid != 0 && !ignored {
+                // Explicit resume marker: the last input happened `idle_s` seconds ago, which is
+                // the precise wake moment (the next app_active is only sent on this poll).
+                let wake = Utc::now() - chrono::Duration::seconds(idle_s as i64);
+                let ts = wake.to_rfc3339_opts(SecondsFormat::Millis, true);
+                let payload = ResumeEvent {
+                    v: 1,
+                    ts: &ts,
+                    source: "windows_collector",
+                    event: "resume",
+                    app: &app,
+                    idle_seconds: idle_s,
+                };
+                info!("resumed from idle: {app}");
+                if let Err(e) = client.post(&endpoint).json(&payload).send().await {
+                    error!("post failed: {e}");
+                }
+                was_idle = false;
+            }
+
+            if pid != 0 && !ignored {
                 let title_for_key = if args.send_title {
                     title.clone()
                 } else {
@@ -186,7 +318,14 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
                 let key = (app.clone(), pid, title_for_key);
                 let due_heartbeat =
                     last_sent_at.elapsed() >= Duration::from_secs(args.heartbeat_seconds);
-                if last_key.as_ref() != Some(&key) || due_heartbeat {
+                // Title-only change: `last_key` keeps the old title while debounced, so the
+                // newest title goes out on the first poll past the threshold.
+                let title_only = last_key
+                    .as_ref()
+                    .is_some_and(|(a, p, t)| *a == key.0 && *p == pid && *t != key.2);
+                let debounced = title_only
+                    && last_sent_at.elapsed() < Duration::from_millis(args.title_debounce_ms);
+                if (last_key.as_ref() != Some(&key) && !debounced) || due_heartbeat {
                     let ts = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
                     let payload = AppActiveEvent {
                         v: 1,
@@ -218,7 +357,7 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
         if args.track_audio {
             let preferred_pid = last_audio.as_ref().map(|a| a.pid);
             let mut audio_poll_failed = false;
-            let audio = match active_audio_app(preferred_pid) {
+            let audio = match active_audio_app(preferred_pid, &ignored_exes, &browser_exes) {
                 Ok(v) => v,
                 Err(e) => {
                     // Important: do NOT emit app_audio_stop on a transient polling error.
@@ -311,7 +450,29 @@ async fn windows_main(args: Args) -> anyhow::Result<()> {
             }
         }
 
+        let slept_at = Utc::now();
         sleep(Duration::from_millis(args.poll_ms)).await;
+        let overrun_ms = (Utc::now() - slept_at).num_milliseconds() - args.poll_ms as i64;
+        if is_clock_jump(overrun_ms) {
+            // Sleep/resume or a clock change: without a marker, Core would run the last app on
+            // across the jump. The gap starts where this poll went to sleep (or now, if the clock
+            // went back, so it still precedes the next events).
+            let at = if overrun_ms < 0 { Utc::now() } else { slept_at };
+            let ts = at.to_rfc3339_opts(SecondsFormat::Millis, true);
+            let payload = GapEvent {
+                v: 1,
+                ts: &ts,
+                source: "windows_collector",
+                event: "gap",
+                gap_seconds: overrun_ms / 1000,
+            };
+            info!("clock jump of {}s; sending gap", overrun_ms / 1000);
+            if let Err(e) = client.post(&endpoint).json(&payload).send().await {
+                error!("post failed: {e}");
+            }
+            // Re-announce the foreground app right away instead of at the next heartbeat.
+            last_key = None;
+        }
     }
 }
 
@@ -426,28 +587,18 @@ async fn maybe_notify_due_review_block(
         paused: bool,
     }
 
-    #[derive(serde::Deserialize, Clone)]
-    struct TopItem {
-        kind: String,
-        #[serde(default, alias = "name")]
-        entity: String,
-        #[serde(default)]
-        title: Option<String>,
+    /// `GET /blocks/due/summary`: names and time range are formatted by Core.
+    #[derive(serde::Deserialize)]
+    struct DueTopItem {
+        name: String,
         seconds: i64,
     }
 
     #[derive(serde::Deserialize)]
-    struct BlockSummary {
+    struct DueBlockSummary {
         id: String,
-        start_ts: String,
-        end_ts: String,
-        top_items: Vec<TopItem>,
-    }
-
-    fn format_hhmm(rfc3339: &str) -> String {
-        chrono::DateTime::parse_from_rfc3339(rfc3339)
-            .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
-            .unwrap_or_else(|_| "??:??".to_string())
+        top3: Vec<DueTopItem>,
+        time_range: String,
     }
 
     fn format_duration(seconds: i64) -> String {
@@ -464,42 +615,6 @@ async fn maybe_notify_due_review_block(
         }
     }
 
-    fn display_top_name(it: &TopItem) -> String {
-        if it.kind == "domain" {
-            let title = it.title.as_deref().unwrap_or("").trim();
-            let domain = it.entity.trim();
-            if !title.is_empty() && !domain.is_empty() && domain != "__hidden__" {
-                return format!("{title} ({domain})");
-            }
-            if !title.is_empty() {
-                return title.to_string();
-            }
-            if domain == "__hidden__" {
-                return "(hidden)".to_string();
-            }
-            return if domain.is_empty() {
-                "(unknown)".to_string()
-            } else {
-                domain.to_string()
-            };
-        }
-
-        let raw = it.entity.trim();
-        if raw == "__hidden__" {
-            return "(hidden)".to_string();
-        }
-        if raw.is_empty() {
-            return "(unknown)".to_string();
-        }
-        let base = raw.split(|c| c == '\\' || c == '/').last().unwrap_or(raw);
-        let lower = base.to_lowercase();
-        if lower.ends_with(".exe") {
-            base[..base.len().saturating_sub(4)].to_string()
-        } else {
-            base.to_string()
-        }
-    }
-
     let tracking: TrackingStatus = get_ok(client, &format!("{base_url}/tracking/status")).await?;
     if tracking.paused && !notify_when_paused {
         return Ok(());
@@ -509,9 +624,11 @@ async fn maybe_notify_due_review_block(
     let date = now.format("%Y-%m-%d").to_string();
     let tz_offset_minutes = now.offset().local_minus_utc() / 60;
 
-    let due: Option<BlockSummary> = get_ok_opt(
+    let due: Option<DueBlockSummary> = get_ok_opt(
         client,
-        &format!("{base_url}/blocks/due?date={date}&tz_offset_minutes={tz_offset_minutes}"),
+        &format!(
+            "{base_url}/blocks/due/summary?date={date}&tz_offset_minutes={tz_offset_minutes}"
+        ),
     )
     .await?;
 
@@ -529,16 +646,11 @@ async fn maybe_notify_due_review_block(
         return Ok(());
     }
 
-    let range = format!(
-        "{}–{}",
-        format_hhmm(&due.start_ts),
-        format_hhmm(&due.end_ts)
-    );
+    let range = due.time_range;
     let top = due
-        .top_items
+        .top3
         .iter()
-        .take(3)
-        .map(|it| format!("{} {}", display_top_name(it), format_duration(it.seconds)))
+        .map(|it| format!("{} {}", it.name, format_duration(it.seconds)))
         .collect::<Vec<_>>()
         .join(" · ");
     let top_line = if top.trim().is_empty() {
@@ -687,7 +799,11 @@ fn foreground_app() -> ForegroundApp {
 }
 
 #[cfg(windows)]
-fn active_audio_app(preferred_pid: Option<u32>) -> anyhow::Result<Option<AudioAppInfo>> {
+fn active_audio_app(
+    preferred_pid: Option<u32>,
+    ignored_exes: &std::collections::HashSet<String>,
+    browser_exes: &std::collections::HashSet<String>,
+) -> anyhow::Result<Option<AudioAppInfo>> {
     use std::path::Path;
     use windows::core::Interface;
     use windows::Win32::Media::Audio::{
@@ -748,7 +864,10 @@ fn active_audio_app(preferred_pid: Option<u32>) -> anyhow::Result<Option<AudioAp
             .unwrap_or_else(|| format!("pid:{pid}"));
 
         let lower = app.to_lowercase();
-        if lower == "audiodg.exe" || is_browser_exe(lower.as_str()) {
+        if lower == "audiodg.exe"
+            || is_browser_exe(lower.as_str(), browser_exes)
+            || ignored_exes.contains(&lower)
+        {
             continue;
         }
 
@@ -796,9 +915,33 @@ fn query_process_exe_path(pid: u32) -> Option<String> {
 }
 
 #[cfg(windows)]
-fn is_browser_exe(lower_exe: &str) -> bool {
+fn is_browser_exe(lower_exe: &str, extra: &std::collections::HashSet<String>) -> bool {
     matches!(
         lower_exe,
         "chrome.exe" | "msedge.exe" | "brave.exe" | "vivaldi.exe" | "opera.exe" | "firefox.exe"
-    )
+    ) || extra.contains(lower_exe)
+}
+
+/// `C:\Tools\KeePassXC.exe` / `KeePassXC.exe` -> `keepassxc.exe`.
+#[cfg(windows)]
+fn exe_basename_lower(s: &str) -> String {
+    s.rsplit(['\\', '/']).next().unwrap_or(s).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_jump_tolerance_is_symmetric() {
+        let tolerance_ms = CLOCK_JUMP_TOLERANCE_SECONDS * 1000;
+        assert!(!is_clock_jump(0));
+        assert!(!is_clock_jump(tolerance_ms));
+        assert!(!is_clock_jump(-tolerance_ms));
+        assert!(!is_clock_jump(-1500));
+        assert!(is_clock_jump(tolerance_ms + 1));
+        assert!(is_clock_jump(-tolerance_ms - 1));
+        assert!(is_clock_jump(8 * 3600 * 1000));
+        assert!(is_clock_jump(-3600 * 1000));
+    }
 }
@@ -289,7 +289,9 @@ class CoreClient {
     return DeleteDayResult.fromJson(data);
   }
 
-  Future<WipeAllResult> wipeAllData() async {
+  /// First step of a wipe: deletes nothing, only hands out a short-lived confirm token.
+  /// Pass it to [wipeAllData] once the user has confirmed.
+  Future<WipeConfirm> requestWipeConfirm() async {
     final res = await _http.post(
       _u("/data/wipe"),
       headers: {"content-type": "application/json"},
@@ -301,6 +303,23 @@ class CoreClient {
     final obj = jsonDecode(res.body) as Map<String, dynamic>;
     final data = obj["data"] as Map<String, dynamic>?;
     if (data == null) throw Exception("invalid_response");
+    final confirm = WipeConfirm.fromJson(data);
+    if (confirm.token.isEmpty) throw Exception("invalid_response");
+    return confirm;
+  }
+
+  Future<WipeAllResult> wipeAllData({required String confirmToken}) async {
+    final res = await _http.post(
+      _u("/data/wipe"),
+      headers: {"content-type": "application/json"},
+      body: jsonEncode({"confirm_token": confirmToken}),
+    );
+    if (res.statusCode != 200) {
+      throw Exception("http_${res.statusCode}");
+    }
+    final obj = jsonDecode(res.body) as Map<String, dynamic>;
+    final data = obj["data"] as Map<String, dynamic>?;
+    if (data == null) throw Exception("invalid_response");
     return WipeAllResult.fromJson(data);
   }
 
@@ -952,22 +971,39 @@ class DeleteDayResult {
   }
 }
 
+class WipeConfirm {
+  WipeConfirm({required this.token, required this.expiresInSeconds});
+
+  final String token;
+  final int expiresInSeconds;
+
+  factory WipeConfirm.fromJson(Map<String, dynamic> json) {
+    return WipeConfirm(
+      token: (json["confirm_token"] as String?) ?? "",
+      expiresInSeconds: (json["expires_in_seconds"] as int?) ?? 0,
+    );
+  }
+}
+
 class WipeAllResult {
   WipeAllResult({
     required this.eventsDeleted,
     required this.reviewsDeleted,
     required this.reportsDeleted,
+    this.backupPath,
   });
 
   final int eventsDeleted;
   final int reviewsDeleted;
   final int reportsDeleted;
+  final String? backupPath;
 
   factory WipeAllResult.fromJson(Map<String, dynamic> json) {
     return WipeAllResult(
       eventsDeleted: (json["events_deleted"] as int?) ?? 0,
       reviewsDeleted: (json["reviews_deleted"] as int?) ?? 0,
       reportsDeleted: (json["reports_deleted"] as int?) ?? 0,
+      backupPath: json["backup_path"] as String?,
     );
   }
 }
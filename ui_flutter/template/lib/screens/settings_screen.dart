@@ -1173,12 +1173,23 @@ class SettingsScreenState extends State<SettingsScreen> {
   }
 
   Future<void> _wipeAllData() async {
+    // Core only wipes with a token from a first request; the user confirms in between.
+    final WipeConfirm confirm;
+    try {
+      confirm = await widget.client.requestWipeConfirm();
+    } catch (e) {
+      if (!mounted) return;
+      ScaffoldMessenger.of(context)
+          .showSnackBar(SnackBar(content: Text("Wipe failed: $e")));
+      return;
+    }
+    if (!mounted) return;
     final ok = await showDialog<bool>(
       context: context,
       builder: (ctx) => AlertDialog(
         title: const Text("Wipe ALL data?"),
-        content: const Text(
-          "This will delete all events and all block reviews.\n\nPrivacy rules and Core settings will be kept.\n\nThis cannot be undone.",
+        content: Text(
+          "This will delete all events and all block reviews.\n\nPrivacy rules and Core settings will be kept.\n\nThis cannot be undone. Confirm within ${confirm.expiresInSeconds}s.",
         ),
         actions: [
           TextButton(
@@ -1195,7 +1206,8 @@ class SettingsScreenState extends State<SettingsScreen> {
 
     setState(() => _wipeAllLoading = true);
     try {
-      final res = await widget.client.wipeAllData();
+      final res =
+          await widget.client.wipeAllData(confirmToken: confirm.token);
       if (!mounted) return;
       await _loadEvents();
       ScaffoldMessenger.of(context).showSnackBar(